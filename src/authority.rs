@@ -0,0 +1,56 @@
+use anyhow::{Context, Result};
+use solana_sdk::{
+    bpf_loader_upgradeable::set_upgrade_authority, pubkey::Pubkey,
+    signer::Signer, transaction::Transaction,
+};
+
+use crate::AppConfig;
+
+/// Transfers the upgrade authority of `program` to `new_authority`, or
+/// strips it entirely (making the program permanently immutable) when
+/// `new_authority` is `None`. Requires `config.buffer_authority()` (the fee
+/// payer, unless a distinct buffer authority is configured) to be the
+/// program's current upgrade authority.
+pub fn set_authority(
+    config: &AppConfig,
+    program: &Pubkey,
+    new_authority: Option<&Pubkey>,
+) -> Result<()> {
+    let payer: &dyn Signer = config.authority.as_ref();
+    let current_authority = config.buffer_authority();
+    let blockhash = config
+        .client
+        .get_latest_blockhash()
+        .context("Couldn't get recent blockhash.")?;
+
+    let mut signers = vec![payer];
+    if current_authority.pubkey() != payer.pubkey() {
+        signers.push(current_authority);
+    }
+
+    let ix =
+        set_upgrade_authority(program, &current_authority.pubkey(), new_authority);
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&payer.pubkey()),
+        &signers,
+        blockhash,
+    );
+
+    config
+        .client
+        .send_and_confirm_transaction_with_spinner_and_config(
+            &tx,
+            config.client.commitment(),
+            config.send_config,
+        )
+        .context("Set authority tx error")?;
+
+    Ok(())
+}
+
+/// Makes `program` permanently immutable by clearing its upgrade authority.
+/// This cannot be undone; callers should confirm with the user first.
+pub fn finalize(config: &AppConfig, program: &Pubkey) -> Result<()> {
+    set_authority(config, program, None)
+}
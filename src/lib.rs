@@ -1,4 +1,4 @@
-use anyhow::{anyhow, ensure, Context, Result};
+use anyhow::{anyhow, bail, ensure, Context, Result};
 use crossbeam::thread;
 use crossterm::{cursor, queue, terminal};
 use serde::{Deserialize, Serialize};
@@ -15,42 +15,116 @@ use solana_sdk::{
     message::Message,
     native_token::lamports_to_sol,
     pubkey::Pubkey,
-    signature::{read_keypair_file, Keypair},
+    signature::Keypair,
     signer::Signer,
     transaction::Transaction,
 };
 use std::{
     io::Write,
+    net::UdpSocket,
     path::{Path, PathBuf},
+    sync::atomic::Ordering,
     time::{Duration, Instant},
 };
 
+mod authority;
+mod idl;
+mod reclaim;
+mod signer;
+mod state;
+mod tpu;
 mod utils;
+mod verify;
+pub use authority::*;
+pub use idl::*;
+pub use reclaim::*;
+pub use signer::*;
+pub use state::*;
+pub use tpu::*;
 pub use utils::*;
+pub use verify::*;
 
 #[derive(Serialize, Deserialize)]
 struct Config {
     // TODO: monikers
     pub url: String,
-    pub program: Program,
+    /// One or more programs to deploy, each its own `[[program]]` table. A
+    /// single-program `deploy.toml` is just a workspace with one entry.
+    pub program: Vec<Program>,
     pub options: Options,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct Program {
     // TODO: use solana_cli default
+    /// Signer source for the fee payer / upgrade authority: a local keypair
+    /// JSON path, a `usb://ledger...` hardware wallet locator, or a
+    /// `prompt://`/`seed://` BIP39 seed phrase.
     pub authority: PathBuf,
+    /// Signer source for the program's own keypair. Accepts the same
+    /// schemes as `authority`.
     pub keypair: PathBuf,
     // TODO: search in target/deploy ?
     pub shared_obj: PathBuf,
+    /// Pubkey of an existing buffer account to attach to and resume writing
+    /// into, instead of creating a fresh one.
+    #[serde(default)]
+    pub buffer: Option<Pubkey>,
+    /// Keypair that owns the buffer (and, after deploy, the program) when it
+    /// should differ from the fee payer, e.g. a cold/offline key.
+    #[serde(default)]
+    pub buffer_authority: Option<PathBuf>,
+    /// Path to an Anchor `idl.json` to publish on-chain after a successful
+    /// deploy/upgrade, the same way the `idl` subcommand does. Left unset,
+    /// no IDL is uploaded.
+    #[serde(default)]
+    pub upload_idl: Option<PathBuf>,
+    /// Strip the upgrade authority immediately after a successful
+    /// deploy/upgrade, making the program permanently immutable. Equivalent
+    /// to running `set-authority --final` right after `run`: it is sent as a
+    /// separate transaction once the deploy has confirmed. Irreversible, so
+    /// this still prompts for confirmation before finalizing.
+    #[serde(default)]
+    pub finalize: bool,
 }
 
-#[derive(Serialize, Deserialize)]
+/// Ad hoc overrides for fields normally sourced from `deploy.toml`, applied
+/// after the config file is parsed. Lets a user redeploy to a different
+/// cluster or sign with a different payer without editing the TOML, the
+/// way `anchor deploy --url ... --keypair ...` does.
+#[derive(Default)]
+pub struct ConfigOverrides {
+    /// Overrides `Config::url`.
+    pub url: Option<String>,
+    /// Overrides `Program::authority`.
+    pub keypair: Option<PathBuf>,
+    /// Overrides `Program::shared_obj`.
+    pub program: Option<PathBuf>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Options {
     pub jobs: usize,
     pub max_retries: Option<usize>,
     pub sleep: u64,
     pub timeout: u64,
+    /// Submit write transactions directly to the current and upcoming slot
+    /// leaders' TPU over UDP instead of going through the RPC forwarding hop.
+    #[serde(default)]
+    pub use_tpu: bool,
+    /// Micro-lamports per compute unit to attach as a priority fee to every
+    /// write, deploy, upgrade, and close transaction. Ignored when
+    /// `auto_priority_fee_percentile` is set.
+    #[serde(default)]
+    pub priority_fee_micro_lamports: Option<u64>,
+    /// Compute unit limit to request alongside the priority fee. Left
+    /// unset, the cluster simulates and uses a default limit.
+    #[serde(default)]
+    pub compute_unit_limit: Option<u32>,
+    /// Instead of a fixed price, sample `getRecentPrioritizationFees` and
+    /// use this percentile (0-100) of the non-zero fees observed.
+    #[serde(default)]
+    pub auto_priority_fee_percentile: Option<u8>,
 }
 
 /// Generates a new configuration file using the defaults and tries to find the program keypair and
@@ -91,18 +165,51 @@ pub fn generate_config<W: Write>(writer: &mut W, cwd: &Path) -> Result<()> {
     };
 
     writer.write_all(&toml::to_vec(&Config {
-        program,
+        program: vec![program],
         ..Config::default()
     })?)?;
 
     Ok(())
 }
 
+/// Name of the config file `find_config` looks for when no explicit path is
+/// given on the command line.
+const CONFIG_FILE_NAME: &str = "deploy.toml";
+
+/// Resolves the `deploy.toml` to use: `explicit`, if given, is used as-is;
+/// otherwise walks `start` and its ancestors (like Anchor's
+/// `find_cargo_toml`) until one is found, so the tool works from any
+/// subdirectory of a project.
+pub fn find_config(
+    explicit: Option<PathBuf>,
+    start: &Path,
+) -> Result<PathBuf> {
+    if let Some(path) = explicit {
+        return Ok(path);
+    }
+
+    let mut dir = start;
+    loop {
+        let candidate = dir.join(CONFIG_FILE_NAME);
+        if candidate.is_file() {
+            return Ok(candidate);
+        }
+
+        dir = match dir.parent() {
+            Some(parent) => parent,
+            None => bail!(
+                "Couldn't find {CONFIG_FILE_NAME} in {} or any parent directory.",
+                start.display()
+            ),
+        };
+    }
+}
+
 impl Default for Config {
     fn default() -> Self {
         Self {
             url: String::from("https://localhost:8899"),
-            program: Default::default(),
+            program: vec![Program::default()],
             options: Default::default(),
         }
     }
@@ -115,6 +222,10 @@ impl Default for Options {
             timeout: 30,
             jobs: num_cpus::get(),
             max_retries: Some(9000),
+            use_tpu: false,
+            priority_fee_micro_lamports: None,
+            compute_unit_limit: None,
+            auto_priority_fee_percentile: None,
         }
     }
 }
@@ -125,6 +236,10 @@ impl Default for Program {
             authority: "~/.config/solana/id.json".parse().unwrap(),
             keypair: "./target/deploy/program-keypair.json".parse().unwrap(),
             shared_obj: "./target/deploy/program.so".parse().unwrap(),
+            buffer: None,
+            buffer_authority: None,
+            upload_idl: None,
+            finalize: false,
         }
     }
 }
@@ -133,31 +248,104 @@ impl Default for Program {
 pub struct AppConfig {
     pub url: Url,
     pub program_data: Vec<u8>,
-    pub program_keypair: Keypair,
-    pub authority: Keypair,
+    pub program_keypair: Box<dyn Signer>,
+    pub authority: Box<dyn Signer>,
     pub send_config: RpcSendTransactionConfig,
     pub client: RpcClient,
     pub options: Options,
+    /// Bound once and reused for every raw packet sent over the TPU path,
+    /// so the port isn't re-bound per write transaction.
+    pub tpu_socket: Option<UdpSocket>,
+    /// Pubkey of a buffer account to attach to and resume writing into,
+    /// rather than creating a fresh one.
+    pub existing_buffer: Option<Pubkey>,
+    /// Owner of the buffer (and, after deploy, the program), when it should
+    /// differ from `authority`, which then only pays fees.
+    pub buffer_authority: Option<Box<dyn Signer>>,
+    /// Path to an Anchor `idl.json` to publish after a successful
+    /// deploy/upgrade.
+    pub upload_idl: Option<PathBuf>,
+    /// Strip the upgrade authority immediately after a successful
+    /// deploy/upgrade, making the program permanently immutable. Irreversible,
+    /// so this still prompts for confirmation before finalizing.
+    pub finalize: bool,
 }
 
 impl AppConfig {
-    pub fn parse<P: AsRef<Path>>(p: P) -> Result<Self> {
-        let config: Config = std::fs::read(p)
-            .context("Failed to read config file.")
-            .and_then(|c| {
-                toml::from_slice(&c).context("Failed to parse config file.")
-            })?;
-
-        let expand_and_read_keypair = |p: &Path| -> Result<_> {
-            read_keypair_file(shellexpand::full(&p.to_string_lossy())?.as_ref())
-                .map_err(|e| {
-                    anyhow!(
-                        "Couldn't read keypair file ({}): {e}",
-                        p.to_string_lossy()
-                    )
-                })
-        };
+    /// The signer that owns the buffer and the program, falling back to the
+    /// fee payer when no dedicated buffer authority was configured.
+    pub fn buffer_authority(&self) -> &dyn Signer {
+        self.buffer_authority
+            .as_deref()
+            .unwrap_or_else(|| self.authority.as_ref())
+    }
+
+    /// Parses `deploy.toml` and builds an [`AppConfig`] for its first (or
+    /// only) `[[program]]` entry. Subcommands like `set-authority`, `idl`
+    /// and `verify` act on a signer shared across the whole workspace, so
+    /// they only need this single entry; `run`'s deploy flow uses
+    /// [`Self::parse_workspace`] instead to deploy every program.
+    pub fn parse<P: AsRef<Path>>(
+        p: P,
+        overrides: &ConfigOverrides,
+    ) -> Result<Self> {
+        Self::parse_workspace(p, overrides)?
+            .into_iter()
+            .next()
+            .ok_or_else(|| anyhow!("No program configured in deploy.toml."))
+    }
 
+    /// Parses `deploy.toml` and builds one [`AppConfig`] per `[[program]]`
+    /// entry, in declaration order.
+    pub fn parse_workspace<P: AsRef<Path>>(
+        p: P,
+        overrides: &ConfigOverrides,
+    ) -> Result<Vec<Self>> {
+        let path = p.as_ref();
+        let bytes = std::fs::read(path)
+            .with_context(|| format!("Failed to read {}.", path.display()))?;
+        let mut config: Config = toml::from_slice(&bytes).map_err(|e| {
+            match e.line_col() {
+                Some((line, col)) => anyhow!(
+                    "Failed to parse {}: {e} (line {}, column {}).",
+                    path.display(),
+                    line + 1,
+                    col + 1
+                ),
+                None => anyhow!("Failed to parse {}: {e}", path.display()),
+            }
+        })?;
+
+        ensure!(
+            !config.program.is_empty(),
+            "No program configured in deploy.toml."
+        );
+
+        if let Some(url) = &overrides.url {
+            config.url = url.clone();
+        }
+        if let Some(keypair) = &overrides.keypair {
+            for program in &mut config.program {
+                program.authority = keypair.clone();
+            }
+        }
+        if let Some(shared_obj) = &overrides.program {
+            ensure!(
+                config.program.len() == 1,
+                "--program only applies to a single-program deploy.toml; \
+                 edit the [[program]] entries directly for a workspace."
+            );
+            config.program[0].shared_obj = shared_obj.clone();
+        }
+
+        config
+            .program
+            .iter()
+            .map(|program| Self::build(&config, program))
+            .collect()
+    }
+
+    fn build(config: &Config, program: &Program) -> Result<Self> {
         let client = RpcClient::new_with_timeouts_and_commitment(
             &config.url,
             Duration::from_secs(config.options.timeout),
@@ -171,22 +359,40 @@ impl AppConfig {
             ..Default::default()
         };
 
-        // TODO: setup multiple programs
-        let program = &config.program;
-        let authority = expand_and_read_keypair(&program.authority)
-            .context("Couldn't read program authority keypair.")?;
-        let program_keypair = expand_and_read_keypair(&program.keypair)
-            .context("Couldn't read program keypair.")?;
+        let authority = resolve_signer(&program.authority)
+            .context("Couldn't resolve program authority signer.")?;
+        let program_keypair = resolve_signer(&program.keypair)
+            .context("Couldn't resolve program keypair signer.")?;
         let program_data = read_and_verify_elf(&program.shared_obj)?;
+        let buffer_authority = program
+            .buffer_authority
+            .as_deref()
+            .map(resolve_signer)
+            .transpose()
+            .context("Couldn't resolve buffer authority signer.")?;
+
+        let tpu_socket = if config.options.use_tpu {
+            Some(
+                UdpSocket::bind("0.0.0.0:0")
+                    .context("Failed to bind TPU UDP socket.")?,
+            )
+        } else {
+            None
+        };
 
         Ok(Self {
-            options: config.options,
+            options: config.options.clone(),
             url: Url::parse(&config.url)?,
             send_config,
             client,
             authority,
             program_data,
             program_keypair,
+            tpu_socket,
+            existing_buffer: program.buffer,
+            buffer_authority,
+            upload_idl: program.upload_idl.clone(),
+            finalize: program.finalize,
         })
     }
 }
@@ -211,17 +417,25 @@ pub fn create_buffer_account(config: &AppConfig) -> Result<(Keypair, usize)> {
     let ix = create_buffer(
         &config.authority.pubkey(),
         &buffer_kp.pubkey(),
-        &config.authority.pubkey(),
+        &config.buffer_authority().pubkey(),
         min_balance,
         config.program_data.len(),
     )?;
 
     let blockhash = config.client.get_latest_blockhash()?;
 
+    let payer: &dyn Signer = config.authority.as_ref();
+    let buffer_authority = config.buffer_authority();
+    let buffer_kp_signer: &dyn Signer = &buffer_kp;
+    let mut signers = vec![payer, buffer_kp_signer];
+    if buffer_authority.pubkey() != payer.pubkey() {
+        signers.push(buffer_authority);
+    }
+
     let tx = Transaction::new_signed_with_payer(
         &ix,
         Some(&config.authority.pubkey()),
-        &[&config.authority, &buffer_kp],
+        &signers,
         blockhash,
     );
     config
@@ -236,23 +450,74 @@ pub fn create_buffer_account(config: &AppConfig) -> Result<(Keypair, usize)> {
     Ok((buffer_kp, buffer_sz))
 }
 
+/// Writes `config.program_data` into `buffer_acc`'s chunks, returning
+/// `Ok(true)` once every chunk is confirmed or `Ok(false)` if
+/// `resume.interrupted` fired first, in which case, when
+/// `resume.buffer_keypair` is set, a [`ResumeState`] has been persisted to
+/// `resume.state_path` for the next run to pick up.
 pub fn write_to_buffer_account(
     config: &AppConfig,
     buffer_acc: Pubkey,
     buffer_len: usize,
-) -> Result<()> {
-    let payer = &config.authority;
+    resume: &ResumeContext,
+) -> Result<bool> {
+    if config.options.use_tpu {
+        return write_to_buffer_account_via_tpu(
+            config, buffer_acc, buffer_len, resume,
+        );
+    }
+
+    let payer: &dyn Signer = config.authority.as_ref();
+    let buffer_authority = config.buffer_authority();
     let client = &config.client;
     let program_data = &config.program_data;
     let jobs = config.options.jobs;
+    let priority_ixs = compute_budget_instructions(config)?;
+    let priority_ixs = &priority_ixs;
 
     let chunk_sz = calculate_max_chunk_size(config, buffer_acc)?;
-    let tx_count = buffer_len / chunk_sz + 2;
+    let total_chunks = (program_data.len() + chunk_sz - 1) / chunk_sz;
+
+    // Skip chunks that are already confirmed on-chain, so a second
+    // invocation against the same buffer only uploads what's missing.
+    let pending_offsets =
+        diff_buffer_chunks(client, buffer_acc, program_data, chunk_sz)
+            .unwrap_or_else(|_| {
+                (0..total_chunks).map(|i| (i * chunk_sz) as u32).collect()
+            });
+    let tx_count = pending_offsets.len() + 1;
+    if pending_offsets.len() < total_chunks {
+        println!(
+            "Resuming: {} chunk(s) already confirmed on-chain.",
+            total_chunks - pending_offsets.len()
+        );
+    }
 
     let mut blockhash = client.get_latest_blockhash()?;
     let mut start_time = Instant::now();
+    let mut highest_offset = 0u32;
+
+    for (i, offsets) in pending_offsets.chunks(jobs).enumerate() {
+        if resume.interrupted.load(Ordering::SeqCst) {
+            if let Some(buffer_kp) = resume.buffer_keypair {
+                save_resume_state(
+                    resume.state_path,
+                    &ResumeState::new(
+                        buffer_kp,
+                        program_data.len(),
+                        highest_offset,
+                    ),
+                )?;
+                println!(
+                    "Interrupted: saved resume state to {:?}",
+                    resume.state_path
+                );
+            } else {
+                println!("Interrupted.");
+            }
+            return Ok(false);
+        }
 
-    for (i, chunks) in program_data.chunks(chunk_sz * jobs).enumerate() {
         if start_time.elapsed().as_secs() > 30 {
             start_time = Instant::now();
             blockhash = client
@@ -261,28 +526,34 @@ pub fn write_to_buffer_account(
         };
 
         let result = thread::scope(move |s| {
-            for j in 0..config.options.jobs {
-                let total_index = i * config.options.jobs + j;
+            for (j, &offset) in offsets.iter().enumerate() {
+                let total_index = i * jobs + j;
 
                 s.spawn(move |_| -> Result<()> {
-                    let offset = (total_index * chunk_sz) as u32;
-                    if offset >= program_data.len() as u32 {
-                        return Ok(());
-                    }
-
                     let mut stdout = std::io::stdout();
 
-                    let bytes = chunks
-                        .chunks(chunk_sz)
-                        .nth(j)
-                        .ok_or_else(|| anyhow!("Failed to read thread chunk"))?
-                        .to_vec();
+                    let bytes = program_data[offset as usize..]
+                        .iter()
+                        .take(chunk_sz)
+                        .copied()
+                        .collect();
+                    let mut ixs = priority_ixs.clone();
+                    ixs.push(write(
+                        &buffer_acc,
+                        &buffer_authority.pubkey(),
+                        offset,
+                        bytes,
+                    ));
                     let msg = Message::new_with_blockhash(
-                        &[write(&buffer_acc, &payer.pubkey(), offset, bytes)],
+                        &ixs,
                         Some(&payer.pubkey()),
                         &blockhash,
                     );
-                    let tx = Transaction::new(&[payer], msg, blockhash);
+                    let tx = if buffer_authority.pubkey() == payer.pubkey() {
+                        Transaction::new(&[payer], msg, blockhash)
+                    } else {
+                        Transaction::new(&[payer, buffer_authority], msg, blockhash)
+                    };
                     let tx_sig = send_and_confirm_transaction_with_config(
                         client,
                         &tx,
@@ -319,19 +590,162 @@ pub fn write_to_buffer_account(
         if result.is_err() {
             close_buffer_account(config, buffer_acc)?;
         }
+
+        highest_offset = offsets.last().copied().unwrap_or(highest_offset);
     }
 
-    Ok(())
+    Ok(true)
+}
+
+/// Same chunk-write as [`write_to_buffer_account`] but fires each write
+/// transaction straight at the slot leaders' TPU over UDP, re-queueing
+/// whatever is still unconfirmed after `options.timeout` with a fresh
+/// blockhash rather than polling one confirmation per chunk.
+fn write_to_buffer_account_via_tpu(
+    config: &AppConfig,
+    buffer_acc: Pubkey,
+    buffer_len: usize,
+    resume: &ResumeContext,
+) -> Result<bool> {
+    let payer: &dyn Signer = config.authority.as_ref();
+    let buffer_authority = config.buffer_authority();
+    let client = &config.client;
+    let program_data = &config.program_data;
+    let socket = config
+        .tpu_socket
+        .as_ref()
+        .ok_or_else(|| anyhow!("TPU socket not initialized"))?;
+    let priority_ixs = compute_budget_instructions(config)?;
+
+    let chunk_sz = calculate_max_chunk_size(config, buffer_acc)?;
+    let total_chunks = (program_data.len() + chunk_sz - 1) / chunk_sz;
+
+    // Skip chunks that are already confirmed on-chain, so a second
+    // invocation against the same buffer only uploads what's missing.
+    let starting_offsets =
+        diff_buffer_chunks(client, buffer_acc, program_data, chunk_sz)
+            .unwrap_or_else(|_| {
+                (0..total_chunks).map(|i| (i * chunk_sz) as u32).collect()
+            });
+    let tx_count = starting_offsets.len() + 1;
+    if starting_offsets.len() < total_chunks {
+        println!(
+            "Resuming: {} chunk(s) already confirmed on-chain.",
+            total_chunks - starting_offsets.len()
+        );
+    }
+
+    let mut offsets = starting_offsets.clone();
+    let mut blockhash = client.get_latest_blockhash()?;
+    let leaders = resolve_leader_tpu_addresses(client)?;
+    ensure!(!leaders.is_empty(), "Couldn't resolve any TPU leaders.");
+
+    let mut confirmed = 0;
+    while !offsets.is_empty() {
+        if resume.interrupted.load(Ordering::SeqCst) {
+            if let Some(buffer_kp) = resume.buffer_keypair {
+                // Highest offset among the chunks this invocation started
+                // with that are no longer outstanding, i.e. actually
+                // confirmed written, not just still queued for retry.
+                let highest_offset = starting_offsets
+                    .iter()
+                    .filter(|o| !offsets.contains(o))
+                    .max()
+                    .copied()
+                    .unwrap_or(0);
+                save_resume_state(
+                    resume.state_path,
+                    &ResumeState::new(
+                        buffer_kp,
+                        program_data.len(),
+                        highest_offset,
+                    ),
+                )?;
+                println!(
+                    "Interrupted: saved resume state to {:?}",
+                    resume.state_path
+                );
+            } else {
+                println!("Interrupted.");
+            }
+            return Ok(false);
+        }
+
+        let mut pending = Vec::with_capacity(offsets.len());
+
+        for &offset in &offsets {
+            let bytes = program_data[offset as usize..]
+                .iter()
+                .take(chunk_sz)
+                .copied()
+                .collect();
+            let mut ixs = priority_ixs.clone();
+            ixs.push(write(&buffer_acc, &buffer_authority.pubkey(), offset, bytes));
+            let msg = Message::new_with_blockhash(
+                &ixs,
+                Some(&payer.pubkey()),
+                &blockhash,
+            );
+            let tx = if buffer_authority.pubkey() == payer.pubkey() {
+                Transaction::new(&[payer], msg, blockhash)
+            } else {
+                Transaction::new(&[payer, buffer_authority], msg, blockhash)
+            };
+            send_transaction_to_leaders(socket, &tx, &leaders)?;
+            pending.push(PendingWrite {
+                signature: tx.signatures[0],
+                offset,
+            });
+        }
+
+        let start_time = Instant::now();
+        loop {
+            std::thread::sleep(Duration::from_millis(config.options.sleep));
+            let unconfirmed = poll_pending_writes(client, &pending)?;
+            let newly_confirmed = pending.len() - unconfirmed.len();
+            if newly_confirmed > 0 {
+                confirmed += newly_confirmed;
+                term_print(&format!(
+                    "Confirmed ({confirmed}/{tx_count}) via TPU"
+                ))?;
+            }
+
+            if unconfirmed.is_empty() {
+                offsets.clear();
+                break;
+            }
+
+            if start_time.elapsed().as_secs() > config.options.timeout {
+                offsets = unconfirmed;
+                blockhash = client
+                    .get_latest_blockhash()
+                    .context("Couldn't get recent blockhash")?;
+                break;
+            }
+
+            pending.retain(|p| unconfirmed.contains(&p.offset));
+        }
+    }
+
+    Ok(true)
 }
 
 pub fn deploy_or_upgrade_program(
     config: &AppConfig,
     buffer_acc: Pubkey,
+    is_final: bool,
 ) -> Result<()> {
     let client = &config.client;
-    let program = &config.program_keypair;
-    let payer = &config.authority;
+    let program: &dyn Signer = config.program_keypair.as_ref();
+    let payer: &dyn Signer = config.authority.as_ref();
+    let buffer_authority = config.buffer_authority();
 
+    let mut signers = vec![payer];
+    if buffer_authority.pubkey() != payer.pubkey() {
+        signers.push(buffer_authority);
+    }
+
+    let priority_ixs = compute_budget_instructions(config)?;
     let program_acc = client.get_account(&program.pubkey());
     let blockhash = client
         .get_latest_blockhash()
@@ -347,33 +761,37 @@ pub fn deploy_or_upgrade_program(
                 )
                 .context("Couldn't get balance for program.")?;
 
-            let ixs = deploy_with_max_program_len(
+            let mut ixs = priority_ixs;
+            ixs.extend(deploy_with_max_program_len(
                 &payer.pubkey(),
                 &program.pubkey(),
                 &buffer_acc,
-                &payer.pubkey(),
+                &buffer_authority.pubkey(),
                 program_lamports,
                 config.program_data.len() * 2,
-            )?;
+            )?);
 
+            signers.push(program);
             Transaction::new_signed_with_payer(
                 &ixs,
                 Some(&payer.pubkey()),
-                &[payer, program],
+                &signers,
                 blockhash,
             )
         }
         Ok(_) => {
             println!("Upgrading {}", program.pubkey());
+            let mut ixs = priority_ixs;
+            ixs.push(upgrade(
+                &program.pubkey(),
+                &buffer_acc,
+                &buffer_authority.pubkey(),
+                &payer.pubkey(),
+            ));
             Transaction::new_signed_with_payer(
-                &[upgrade(
-                    &program.pubkey(),
-                    &buffer_acc,
-                    &payer.pubkey(),
-                    &payer.pubkey(),
-                )],
+                &ixs,
                 Some(&payer.pubkey()),
-                &[payer],
+                &signers,
                 blockhash,
             )
         }
@@ -385,6 +803,26 @@ pub fn deploy_or_upgrade_program(
         config.send_config,
     )?;
 
+    if is_final {
+        let proceed = confirm(&format!(
+            "This permanently removes the upgrade authority of {}, making it \
+             immutable. Continue?",
+            program.pubkey()
+        ))?;
+        if proceed {
+            println!(
+                "Finalizing {}: program is now immutable.",
+                program.pubkey()
+            );
+            finalize(config, &program.pubkey())?;
+        } else {
+            println!(
+                "Skipped finalize; {} still has an upgrade authority.",
+                program.pubkey()
+            );
+        }
+    }
+
     Ok(())
 }
 
@@ -393,13 +831,14 @@ pub fn close_buffer_account(
     buffer_acc: Pubkey,
 ) -> Result<()> {
     let client = &config.client;
-    let payer = &config.authority;
+    let payer: &dyn Signer = config.authority.as_ref();
     let blockhash = client
         .get_latest_blockhash()
         .context("Failed to fetch latest blockhash.")?;
-    let close_ix = close(&buffer_acc, &payer.pubkey(), &payer.pubkey());
+    let mut ixs = compute_budget_instructions(config)?;
+    ixs.push(close(&buffer_acc, &payer.pubkey(), &payer.pubkey()));
     let close_tx = Transaction::new_signed_with_payer(
-        &[close_ix],
+        &ixs,
         Some(&payer.pubkey()),
         &[payer],
         blockhash,
@@ -1,15 +1,36 @@
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
 use clap::Parser;
-use std::{fs::File, path::PathBuf, time::Instant};
+use solana_sdk::{
+    bpf_loader_upgradeable::UpgradeableLoaderState,
+    native_token::lamports_to_sol, pubkey::Pubkey, signer::Signer,
+};
+use std::{
+    fs::File,
+    path::{Path, PathBuf},
+    sync::{atomic::AtomicBool, Arc},
+    time::Instant,
+};
 
 use solana_deployer::*;
 
 #[derive(Parser)]
 /// Deploy Solana programs during high load.
 pub struct Args {
-    #[clap(short, long = "config", default_value = "deploy.toml")]
-    /// Path to the deploy configuration file.
-    config_path: PathBuf,
+    #[clap(short, long = "config")]
+    /// Path to the deploy configuration file. Defaults to the nearest
+    /// `deploy.toml` found by walking up from the current directory.
+    config_path: Option<PathBuf>,
+    #[clap(long)]
+    /// Override the cluster RPC URL from the config file.
+    url: Option<String>,
+    #[clap(long)]
+    /// Override the fee payer / upgrade authority signer source from the
+    /// config file.
+    keypair: Option<PathBuf>,
+    #[clap(long)]
+    /// Override the program's shared object (.so) path from the config
+    /// file.
+    program: Option<PathBuf>,
     #[clap(subcommand)]
     subcommands: Option<SubCommands>,
 }
@@ -22,19 +43,65 @@ enum SubCommands {
         /// Output filename.
         output: Option<String>,
     },
+    /// Transfers a program's upgrade authority, or revokes it entirely.
+    /// Signs with the first `[[program]]` entry's authority as fee payer and
+    /// its `buffer_authority` (the authority, if unset) as the current
+    /// upgrade authority.
+    SetAuthority {
+        /// Program whose upgrade authority should change.
+        program: Pubkey,
+        /// New upgrade authority. Omitted when `--final` is passed.
+        new_authority: Option<Pubkey>,
+        #[clap(long = "final")]
+        /// Strip the upgrade authority, making the program permanently
+        /// immutable. Irreversible.
+        final_: bool,
+    },
+    /// Finds buffer accounts left behind by aborted deploys and closes them
+    /// to refund their rent-exempt SOL. Also clears the local resume state
+    /// file (see `run`'s Ctrl-C handling) if it points at a buffer that
+    /// gets closed here.
+    #[clap(alias = "recover", alias = "close-buffers")]
+    Reclaim {
+        #[clap(long)]
+        /// List orphaned buffers without closing them.
+        dry_run: bool,
+        #[clap(long)]
+        /// Close every orphaned buffer without prompting for confirmation.
+        all: bool,
+    },
+    /// Publishes an Anchor IDL JSON file on-chain for a deployed program.
+    Idl {
+        /// Path to the IDL JSON file.
+        idl_path: PathBuf,
+        /// Program the IDL describes.
+        program_id: Pubkey,
+    },
+    /// Compares a deployed program's on-chain bytecode against the local
+    /// `.so` referenced in the config, to confirm a high-load deploy
+    /// actually landed the intended binary.
+    Verify {
+        /// Program to verify. Defaults to the config's own program keypair.
+        program_id: Option<Pubkey>,
+    },
 }
 
 fn main() -> Result<()> {
     let args = Args::parse();
     let start_ts = Instant::now();
+    let overrides = ConfigOverrides {
+        url: args.url,
+        keypair: args.keypair,
+        program: args.program,
+    };
 
-    if let Some(SubCommands::GenConfig { output }) = args.subcommands {
+    if let Some(SubCommands::GenConfig { output }) = &args.subcommands {
         let cwd = std::env::current_dir()?;
         if let Some(filename) = output {
             let mut fd = File::options()
                 .write(true)
                 .create_new(true)
-                .open(&filename)
+                .open(filename)
                 .context("Failed to create config file.")?;
             println!("Writing contents to {}.", filename);
             return generate_config(&mut fd, &cwd);
@@ -42,7 +109,111 @@ fn main() -> Result<()> {
         return generate_config(&mut std::io::stdout(), &cwd);
     }
 
-    match run(&args.config_path) {
+    let config_path = find_config(args.config_path, &std::env::current_dir()?)?;
+
+    match args.subcommands {
+        Some(SubCommands::GenConfig { .. }) => unreachable!("handled above"),
+        Some(SubCommands::SetAuthority {
+            program,
+            new_authority,
+            final_,
+        }) => {
+            let config = AppConfig::parse(&config_path, &overrides)?;
+
+            if final_ {
+                let proceed = confirm(&format!(
+                    "This permanently removes the upgrade authority of {program}. Continue?"
+                ))?;
+                if !proceed {
+                    println!("Aborted.");
+                    return Ok(());
+                }
+                finalize(&config, &program)?;
+                println!("{program} is now immutable.");
+            } else {
+                let new_authority = new_authority.context(
+                    "Provide a new authority, or pass --final to revoke it.",
+                )?;
+                set_authority(&config, &program, Some(&new_authority))?;
+                println!("Transferred upgrade authority of {program} to {new_authority}.");
+            }
+
+            return Ok(());
+        }
+        Some(SubCommands::Reclaim { dry_run, all }) => {
+            let configs =
+                AppConfig::parse_workspace(&config_path, &overrides)?;
+            let config = &configs[0];
+            let buffers = find_orphaned_buffers(config)?;
+
+            if buffers.is_empty() {
+                println!("No orphaned buffer accounts found.");
+                return Ok(());
+            }
+
+            let total_lamports: u64 = buffers.iter().map(|b| b.lamports).sum();
+            for buffer in &buffers {
+                println!(
+                    "{} - {} SOL",
+                    buffer.pubkey,
+                    lamports_to_sol(buffer.lamports)
+                );
+            }
+            println!(
+                "{} buffer(s) found, {} SOL reclaimable.",
+                buffers.len(),
+                lamports_to_sol(total_lamports)
+            );
+
+            if dry_run {
+                return Ok(());
+            }
+
+            if !all
+                && !confirm(
+                    "Close all of the above buffer accounts and reclaim their SOL?",
+                )?
+            {
+                println!("Aborted.");
+                return Ok(());
+            }
+
+            close_orphaned_buffers(config, &buffers)?;
+            println!("Reclaimed {} SOL.", lamports_to_sol(total_lamports));
+
+            for i in 0..configs.len() {
+                let state_path =
+                    state_file_path(&config_path, i, configs.len());
+                if let Some(state) = load_resume_state(&state_path)? {
+                    if buffers.iter().any(|b| b.pubkey == state.buffer_pubkey)
+                    {
+                        clear_resume_state(&state_path)?;
+                    }
+                }
+            }
+
+            return Ok(());
+        }
+        Some(SubCommands::Idl {
+            idl_path,
+            program_id,
+        }) => {
+            let config = AppConfig::parse(&config_path, &overrides)?;
+            upload_idl(&config, &program_id, &idl_path)?;
+            return Ok(());
+        }
+        Some(SubCommands::Verify { program_id }) => {
+            let config = AppConfig::parse(&config_path, &overrides)?;
+            let program_id =
+                program_id.unwrap_or_else(|| config.program_keypair.pubkey());
+            let result = verify_program(&config, &program_id)?;
+            report_verify_result(&program_id, result)?;
+            return Ok(());
+        }
+        None => {}
+    }
+
+    match run(&config_path, overrides) {
         Ok(_) => println!(
             "✅ Success! Completed in {}s",
             start_ts.elapsed().as_secs()
@@ -52,3 +223,110 @@ fn main() -> Result<()> {
 
     Ok(())
 }
+
+/// Deploys or upgrades every program in `deploy.toml`'s workspace in
+/// sequence, reporting a per-program success/failure summary at the end.
+/// Ctrl-C is caught once for the whole run rather than killing the process
+/// outright, so whichever program is mid-write gets a chance to persist a
+/// [`ResumeState`] before the run stops.
+fn run(config_path: &Path, overrides: ConfigOverrides) -> Result<()> {
+    let configs = AppConfig::parse_workspace(config_path, &overrides)?;
+    let total = configs.len();
+    let interrupted = register_interrupt_handler()?;
+    let mut failed = Vec::new();
+
+    for (i, config) in configs.iter().enumerate() {
+        let program_id = config.program_keypair.pubkey();
+        println!("==> [{}/{total}] Deploying {program_id}", i + 1);
+
+        match deploy_one(config_path, config, i, total, &interrupted) {
+            Ok(true) => {}
+            Ok(false) => {
+                println!("Interrupted, stopping workspace deploy.");
+                break;
+            }
+            Err(e) => {
+                eprintln!("==> [{}/{total}] {program_id} failed: {e}", i + 1);
+                failed.push(program_id);
+            }
+        }
+    }
+
+    if !failed.is_empty() {
+        bail!(
+            "{} of {total} program(s) failed to deploy: {failed:?}",
+            failed.len()
+        );
+    }
+
+    Ok(())
+}
+
+/// Deploys or upgrades a single program, sourcing its buffer account, in
+/// order of preference, from: `existing_buffer` configured in
+/// `deploy.toml`, a [`ResumeState`] left behind by an interrupted previous
+/// run, or (the default) a freshly created one. Returns `Ok(false)` if
+/// `interrupted` fired mid-write instead of completing the deploy.
+fn deploy_one(
+    config_path: &Path,
+    config: &AppConfig,
+    program_index: usize,
+    total_programs: usize,
+    interrupted: &Arc<AtomicBool>,
+) -> Result<bool> {
+    let state_path =
+        state_file_path(config_path, program_index, total_programs);
+
+    let resumed = load_resume_state(&state_path)?
+        .filter(|state| state.program_data_len == config.program_data.len());
+
+    let owned_buffer_kp;
+    let (buffer_acc, buffer_len, buffer_kp) = match (
+        config.existing_buffer,
+        resumed,
+    ) {
+        (Some(buffer_acc), _) => (
+            buffer_acc,
+            UpgradeableLoaderState::buffer_len(config.program_data.len())?,
+            None,
+        ),
+        (None, Some(state)) => {
+            println!(
+                "Resuming interrupted deploy into buffer {} (highest confirmed chunk offset: {}).",
+                state.buffer_pubkey, state.highest_offset
+            );
+            owned_buffer_kp = state.buffer_keypair()?;
+            (
+                state.buffer_pubkey,
+                UpgradeableLoaderState::buffer_len(config.program_data.len())?,
+                Some(&owned_buffer_kp),
+            )
+        }
+        (None, None) => {
+            let (buffer_kp, buffer_len) = create_buffer_account(config)?;
+            let pubkey = buffer_kp.pubkey();
+            owned_buffer_kp = buffer_kp;
+            (pubkey, buffer_len, Some(&owned_buffer_kp))
+        }
+    };
+
+    let resume = ResumeContext {
+        state_path: &state_path,
+        buffer_keypair: buffer_kp,
+        interrupted: interrupted.clone(),
+    };
+
+    if !write_to_buffer_account(config, buffer_acc, buffer_len, &resume)? {
+        return Ok(false);
+    }
+
+    deploy_or_upgrade_program(config, buffer_acc, config.finalize)?;
+
+    if let Some(idl_path) = &config.upload_idl {
+        upload_idl(config, &config.program_keypair.pubkey(), idl_path)?;
+    }
+
+    clear_resume_state(&state_path)?;
+
+    Ok(true)
+}
@@ -0,0 +1,69 @@
+use anyhow::{Context, Result};
+use solana_client::{
+    rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig},
+    rpc_filter::{Memcmp, MemcmpEncodedBytes, RpcFilterType},
+};
+use solana_sdk::{
+    bpf_loader_upgradeable::{self, UpgradeableLoaderState},
+    pubkey::Pubkey,
+    signer::Signer,
+};
+
+use crate::AppConfig;
+
+/// A buffer account left behind by an aborted deploy: still owned by
+/// `config.authority` and holding rent-exempt lamports that can be
+/// reclaimed by closing it.
+pub struct OrphanedBuffer {
+    pub pubkey: Pubkey,
+    pub lamports: u64,
+}
+
+/// Lists every buffer account on-chain whose authority is
+/// `config.authority`, via a `getProgramAccounts` memcmp filter matching
+/// the loader's `Buffer { authority_address: Some(authority) }` header.
+pub fn find_orphaned_buffers(config: &AppConfig) -> Result<Vec<OrphanedBuffer>> {
+    let header = bincode::serialize(&UpgradeableLoaderState::Buffer {
+        authority_address: Some(config.authority.pubkey()),
+    })?;
+
+    let rpc_config = RpcProgramAccountsConfig {
+        filters: Some(vec![RpcFilterType::Memcmp(Memcmp {
+            offset: 0,
+            bytes: MemcmpEncodedBytes::Bytes(header),
+            encoding: None,
+        })]),
+        account_config: RpcAccountInfoConfig::default(),
+        with_context: None,
+    };
+
+    let accounts = config
+        .client
+        .get_program_accounts_with_config(
+            &bpf_loader_upgradeable::id(),
+            rpc_config,
+        )
+        .context("Couldn't list buffer accounts.")?;
+
+    Ok(accounts
+        .into_iter()
+        .map(|(pubkey, account)| OrphanedBuffer {
+            pubkey,
+            lamports: account.lamports,
+        })
+        .collect())
+}
+
+/// Closes every buffer in `buffers` one by one, refunding its rent-exempt
+/// lamports to `config.authority`. Bails on the first failure, leaving the
+/// remaining buffers untouched so the caller can retry.
+pub fn close_orphaned_buffers(
+    config: &AppConfig,
+    buffers: &[OrphanedBuffer],
+) -> Result<()> {
+    for buffer in buffers {
+        crate::close_buffer_account(config, buffer.pubkey)
+            .with_context(|| format!("Couldn't close buffer {}", buffer.pubkey))?;
+    }
+    Ok(())
+}
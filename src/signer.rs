@@ -0,0 +1,95 @@
+use anyhow::{anyhow, Context, Result};
+use bip39::{Language, Mnemonic, Seed};
+use solana_remote_wallet::{
+    locator::Locator,
+    remote_keypair::RemoteKeypair,
+    remote_wallet::{initialize_wallet_manager, RemoteWalletType},
+};
+use solana_sdk::{
+    derivation_path::DerivationPath,
+    signature::{read_keypair_file, Signer},
+    signer::keypair::keypair_from_seed_and_derivation_path,
+};
+use std::path::Path;
+
+/// Resolves a signer source from the config file into a boxed [`Signer`]:
+/// a `usb://ledger...` locator routed through a connected hardware wallet,
+/// a `prompt://`/`seed://` BIP39 seed phrase prompted for interactively, or
+/// (the default) a local keypair JSON file. This lets `authority`,
+/// `buffer_authority`, and `keypair` all be cold/offline keys that never
+/// touch disk.
+pub fn resolve_signer(path: &Path) -> Result<Box<dyn Signer>> {
+    let raw = path.to_string_lossy();
+
+    if let Some(uri) = raw.strip_prefix("usb://") {
+        return resolve_remote_wallet_signer(uri);
+    }
+
+    if raw.starts_with("prompt://") || raw.starts_with("seed://") {
+        return resolve_seed_phrase_signer(&raw);
+    }
+
+    let expanded = shellexpand::full(&raw)?;
+    read_keypair_file(expanded.as_ref())
+        .map(|kp| Box::new(kp) as Box<dyn Signer>)
+        .map_err(|e| anyhow!("Couldn't read keypair file ({raw}): {e}"))
+}
+
+/// Resolves a `usb://ledger[<pubkey>][?key=<derivation path>]` locator into
+/// a [`RemoteKeypair`] backed by a connected Ledger device.
+fn resolve_remote_wallet_signer(uri: &str) -> Result<Box<dyn Signer>> {
+    let locator = Locator::new_from_path(format!("usb://{uri}"))
+        .with_context(|| format!("Invalid hardware wallet locator: usb://{uri}"))?;
+    let derivation_path = locator.derivation_path.clone().unwrap_or_default();
+
+    let wallet_manager = initialize_wallet_manager().context(
+        "Couldn't initialize hardware wallet manager. Is a Ledger connected and unlocked?",
+    )?;
+    let device_path = wallet_manager
+        .list_devices()
+        .iter()
+        .find(|info| locator.pubkey.is_none() || locator.pubkey == Some(info.pubkey))
+        .map(|info| info.get_pretty_path())
+        .ok_or_else(|| anyhow!("No matching Ledger device found for usb://{uri}"))?;
+    let ledger = wallet_manager
+        .get_ledger(&device_path)
+        .context("Couldn't open Ledger device.")?;
+
+    let keypair = RemoteKeypair::new(
+        RemoteWalletType::Ledger(ledger),
+        derivation_path,
+        locator.pubkey,
+        uri.to_string(),
+    )
+    .context("Couldn't resolve Ledger signer.")?;
+
+    Ok(Box::new(keypair))
+}
+
+/// Resolves a `prompt://`/`seed://[?key=<derivation path>]` source by
+/// prompting for a BIP39 seed phrase (and optional passphrase) and
+/// deriving a [`Keypair`] from it, the same way `solana-keygen recover`
+/// does.
+fn resolve_seed_phrase_signer(uri: &str) -> Result<Box<dyn Signer>> {
+    let derivation_path = uri
+        .split_once('?')
+        .and_then(|(_, query)| query.strip_prefix("key="))
+        .map(|path| path.parse::<DerivationPath>())
+        .transpose()
+        .context("Invalid derivation path in seed URI.")?;
+
+    let phrase =
+        rpassword::prompt_password("Seed phrase: ").context("Couldn't read seed phrase.")?;
+    let passphrase = rpassword::prompt_password("BIP39 passphrase (optional): ")
+        .context("Couldn't read BIP39 passphrase.")?;
+
+    let mnemonic = Mnemonic::from_phrase(phrase.trim(), Language::English)
+        .map_err(|e| anyhow!("Invalid BIP39 seed phrase: {e}"))?;
+    let seed = Seed::new(&mnemonic, &passphrase);
+
+    let keypair =
+        keypair_from_seed_and_derivation_path(seed.as_bytes(), derivation_path)
+            .map_err(|e| anyhow!("Couldn't derive keypair from seed phrase: {e}"))?;
+
+    Ok(Box::new(keypair))
+}
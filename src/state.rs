@@ -0,0 +1,144 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use solana_sdk::{pubkey::Pubkey, signature::Keypair, signer::Signer};
+use std::{
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+
+/// Snapshot of an in-progress buffer write, persisted next to the config
+/// file so an interrupted deploy can resume writing only the missing
+/// chunks instead of paying to reallocate a fresh buffer account.
+///
+/// Invariant: `buffer_keypair` must be preserved byte-for-byte across runs.
+/// A resumed run parses it back (a corrupt one fails the resume) and
+/// persists it again if that run is interrupted too. It isn't needed to
+/// sign write transactions (only `buffer_authority` is) or close ones (the
+/// fee payer is).
+#[derive(Serialize, Deserialize)]
+pub struct ResumeState {
+    pub buffer_pubkey: Pubkey,
+    buffer_keypair: Vec<u8>,
+    /// Length of the program binary this buffer was sized for, used to
+    /// sanity-check that a resumed state file still matches `.so` on disk.
+    pub program_data_len: usize,
+    /// Highest chunk offset confirmed written before the run was
+    /// interrupted.
+    pub highest_offset: u32,
+}
+
+impl ResumeState {
+    pub fn new(
+        buffer_kp: &Keypair,
+        program_data_len: usize,
+        highest_offset: u32,
+    ) -> Self {
+        Self {
+            buffer_pubkey: buffer_kp.pubkey(),
+            buffer_keypair: buffer_kp.to_bytes().to_vec(),
+            program_data_len,
+            highest_offset,
+        }
+    }
+
+    pub fn buffer_keypair(&self) -> Result<Keypair> {
+        Keypair::from_bytes(&self.buffer_keypair)
+            .context("Corrupt buffer keypair in resume state file.")
+    }
+}
+
+/// Path of the resume state file for the `program_index`-th entry of a
+/// `deploy.toml` workspace of `total_programs` programs: sits alongside it
+/// as `<config>.state.json`, or `<config>.program<N>.state.json` when the
+/// workspace has more than one program so each gets its own.
+pub fn state_file_path(
+    config_path: &Path,
+    program_index: usize,
+    total_programs: usize,
+) -> PathBuf {
+    let mut path = config_path.as_os_str().to_owned();
+    if total_programs > 1 {
+        path.push(format!(".program{program_index}"));
+    }
+    path.push(".state.json");
+    PathBuf::from(path)
+}
+
+/// Loads a previously persisted [`ResumeState`], if one exists.
+pub fn load_resume_state(path: &Path) -> Result<Option<ResumeState>> {
+    if !path.exists() {
+        return Ok(None);
+    }
+
+    let bytes = std::fs::read(path)
+        .with_context(|| format!("Couldn't read state file {path:?}"))?;
+    let state = serde_json::from_slice(&bytes)
+        .with_context(|| format!("Couldn't parse state file {path:?}"))?;
+
+    Ok(Some(state))
+}
+
+pub fn save_resume_state(path: &Path, state: &ResumeState) -> Result<()> {
+    let bytes = serde_json::to_vec_pretty(state)?;
+    std::fs::write(path, bytes)
+        .with_context(|| format!("Couldn't write state file {path:?}"))
+}
+
+/// Removes the resume state file once a deploy completes successfully, so
+/// the next run doesn't try to resume a buffer that's already been
+/// consumed.
+pub fn clear_resume_state(path: &Path) -> Result<()> {
+    if !path.exists() {
+        return Ok(());
+    }
+
+    std::fs::remove_file(path)
+        .with_context(|| format!("Couldn't remove state file {path:?}"))
+}
+
+/// Threaded through the buffer-write loops so they can persist a
+/// [`ResumeState`] and bail out as soon as `interrupted` flips to `true`.
+/// `buffer_keypair` is `None` when `buffer_acc` came from `deploy.toml`'s
+/// `buffer` field rather than being created by this run, in which case we
+/// have no private key to persist and rely on that config field for resume
+/// instead.
+pub struct ResumeContext<'a> {
+    pub state_path: &'a Path,
+    pub buffer_keypair: Option<&'a Keypair>,
+    pub interrupted: Arc<AtomicBool>,
+}
+
+/// Installs a `SIGINT` handler that flips the returned flag instead of
+/// killing the process outright, so the write loop gets a chance to
+/// persist a [`ResumeState`] before exiting.
+pub fn register_interrupt_handler() -> Result<Arc<AtomicBool>> {
+    let interrupted = Arc::new(AtomicBool::new(false));
+    let flag = interrupted.clone();
+
+    ctrlc::set_handler(move || {
+        flag.store(true, Ordering::SeqCst);
+    })
+    .context("Couldn't install Ctrl-C handler.")?;
+
+    Ok(interrupted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn single_program_uses_a_bare_state_file() {
+        let path = state_file_path(Path::new("deploy.toml"), 0, 1);
+        assert_eq!(path, PathBuf::from("deploy.toml.state.json"));
+    }
+
+    #[test]
+    fn workspace_suffixes_each_program_by_index() {
+        let path = state_file_path(Path::new("deploy.toml"), 2, 3);
+        assert_eq!(path, PathBuf::from("deploy.toml.program2.state.json"));
+    }
+}
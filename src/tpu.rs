@@ -0,0 +1,122 @@
+use anyhow::{anyhow, Context, Result};
+use solana_client::{
+    rpc_client::RpcClient, rpc_config::RpcSignatureStatusConfig,
+    rpc_request::MAX_GET_SIGNATURE_STATUSES_QUERY_ITEMS,
+};
+use solana_sdk::{
+    commitment_config::CommitmentConfig, signature::Signature,
+    transaction::Transaction,
+};
+use std::{
+    collections::HashMap,
+    net::{SocketAddr, UdpSocket},
+};
+
+/// Number of upcoming slot leaders whose TPU we fan a write transaction out
+/// to, so a single missed leader doesn't stall the chunk.
+const LEADER_FANOUT: usize = 4;
+
+/// Resolves the TPU socket addresses of the current and next few slot
+/// leaders by cross-referencing `getLeaderSchedule` with `getClusterNodes`.
+pub fn resolve_leader_tpu_addresses(
+    client: &RpcClient,
+) -> Result<Vec<SocketAddr>> {
+    let slot = client.get_slot()?;
+    let schedule = client
+        .get_leader_schedule(Some(slot))?
+        .ok_or_else(|| anyhow!("No leader schedule for slot {slot}"))?;
+
+    let epoch_info = client.get_epoch_info()?;
+    let slot_index = epoch_info.slot_index as usize;
+
+    // Each upcoming leader paired with its first slot in the window, soonest
+    // first, so truncating to `LEADER_FANOUT` keeps the current and next ones.
+    let mut upcoming: Vec<(usize, &String)> = schedule
+        .iter()
+        .filter_map(|(identity, slots)| {
+            slots
+                .iter()
+                .copied()
+                .filter(|s| *s >= slot_index && *s < slot_index + 64)
+                .min()
+                .map(|first_slot| (first_slot, identity))
+        })
+        .collect();
+    upcoming.sort_unstable();
+
+    let nodes = client.get_cluster_nodes()?;
+    let tpu_by_identity: HashMap<&str, SocketAddr> = nodes
+        .iter()
+        .filter_map(|n| n.tpu.map(|tpu| (n.pubkey.as_str(), tpu)))
+        .collect();
+
+    let mut addrs: Vec<SocketAddr> = Vec::new();
+    for (_, identity) in upcoming {
+        if let Some(&tpu) = tpu_by_identity.get(identity.as_str()) {
+            if !addrs.contains(&tpu) {
+                addrs.push(tpu);
+            }
+        }
+    }
+    addrs.truncate(LEADER_FANOUT);
+
+    Ok(addrs)
+}
+
+/// Serializes `tx` and fires it as a raw UDP packet at every resolved leader
+/// TPU, bypassing the RPC forwarding hop entirely.
+pub fn send_transaction_to_leaders(
+    socket: &UdpSocket,
+    tx: &Transaction,
+    leaders: &[SocketAddr],
+) -> Result<()> {
+    let packet = bincode::serialize(tx).context("Failed to serialize tx")?;
+    for leader in leaders {
+        socket
+            .send_to(&packet, leader)
+            .with_context(|| format!("Failed to send packet to {leader}"))?;
+    }
+    Ok(())
+}
+
+/// A write transaction that has been fired over TPU and is awaiting
+/// confirmation.
+pub struct PendingWrite {
+    pub signature: Signature,
+    pub offset: u32,
+}
+
+/// Batches `getSignatureStatuses` lookups across all outstanding writes
+/// instead of confirming them one at a time, returning the offsets of
+/// chunks that are still unconfirmed.
+pub fn poll_pending_writes(
+    client: &RpcClient,
+    pending: &[PendingWrite],
+) -> Result<Vec<u32>> {
+    let mut unconfirmed = Vec::new();
+
+    for batch in pending.chunks(MAX_GET_SIGNATURE_STATUSES_QUERY_ITEMS) {
+        let sigs: Vec<Signature> = batch.iter().map(|p| p.signature).collect();
+        let statuses = client
+            .get_signature_statuses_with_config(
+                &sigs,
+                RpcSignatureStatusConfig {
+                    search_transaction_history: false,
+                },
+            )?
+            .value;
+
+        for (write, status) in batch.iter().zip(statuses) {
+            let confirmed = status
+                .map(|s| {
+                    s.satisfies_commitment(CommitmentConfig::confirmed())
+                })
+                .unwrap_or(false);
+            if !confirmed {
+                unconfirmed.push(write.offset);
+            }
+        }
+    }
+
+    Ok(unconfirmed)
+}
@@ -10,9 +10,17 @@ use solana_client::{
 use solana_program_runtime::invoke_context::InvokeContext;
 use solana_rbpf::{elf, verifier, vm};
 use solana_sdk::{
-    bpf_loader_upgradeable, commitment_config::CommitmentConfig, hash::Hash,
-    message::Message, packet::PACKET_DATA_SIZE, pubkey::Pubkey,
-    signature::Signature, signer::Signer, transaction::Transaction,
+    bpf_loader_upgradeable::{self, UpgradeableLoaderState},
+    commitment_config::CommitmentConfig,
+    compute_budget::ComputeBudgetInstruction,
+    hash::Hash,
+    instruction::Instruction,
+    message::Message,
+    packet::PACKET_DATA_SIZE,
+    pubkey::Pubkey,
+    signature::Signature,
+    signer::Signer,
+    transaction::Transaction,
     transaction_context::TransactionContext,
 };
 use std::{
@@ -23,18 +31,76 @@ use std::{
 
 use crate::AppConfig;
 
+/// Builds the compute-budget instructions to prepend to every write,
+/// deploy, upgrade, and close transaction: a unit price driven by
+/// `Options::priority_fee_micro_lamports` (or sampled live when
+/// `auto_priority_fee_percentile` is set) and, optionally, a unit limit.
+/// Returns an empty vec when no priority fee is configured.
+pub fn compute_budget_instructions(
+    config: &AppConfig,
+) -> Result<Vec<Instruction>> {
+    let mut ixs = Vec::new();
+
+    let price = match config.options.auto_priority_fee_percentile {
+        Some(percentile) => {
+            Some(sample_priority_fee(&config.client, percentile)?)
+        }
+        None => config.options.priority_fee_micro_lamports,
+    };
+
+    if let Some(price) = price {
+        ixs.push(ComputeBudgetInstruction::set_compute_unit_price(price));
+    }
+    if let Some(limit) = config.options.compute_unit_limit {
+        ixs.push(ComputeBudgetInstruction::set_compute_unit_limit(limit));
+    }
+
+    Ok(ixs)
+}
+
+/// Samples `getRecentPrioritizationFees` and returns the given percentile
+/// (0-100) of the non-zero fees observed, for "auto" priority-fee mode.
+fn sample_priority_fee(client: &RpcClient, percentile: u8) -> Result<u64> {
+    let fees: Vec<u64> = client
+        .get_recent_prioritization_fees(&[])
+        .context("Couldn't fetch recent prioritization fees.")?
+        .into_iter()
+        .map(|f| f.prioritization_fee)
+        .filter(|&fee| fee > 0)
+        .collect();
+
+    Ok(fee_percentile(fees, percentile))
+}
+
+/// Returns the given percentile (0-100) of `fees`, or 0 if `fees` is empty.
+fn fee_percentile(mut fees: Vec<u64>, percentile: u8) -> u64 {
+    if fees.is_empty() {
+        return 0;
+    }
+
+    fees.sort_unstable();
+    let idx = (fees.len() - 1) * percentile.min(100) as usize / 100;
+    fees[idx]
+}
+
 pub fn calculate_max_chunk_size(
     config: &AppConfig,
     buffer_acc: Pubkey,
 ) -> Result<usize> {
+    let payer = config.authority.pubkey();
+    let buffer_authority = config.buffer_authority().pubkey();
+
+    let mut ixs = compute_budget_instructions(config)?;
+    ixs.push(bpf_loader_upgradeable::write(
+        &buffer_acc,
+        &buffer_authority,
+        0,
+        vec![],
+    ));
+
     let baseline_msg = Message::new_with_blockhash(
-        &[bpf_loader_upgradeable::write(
-            &buffer_acc,
-            &config.authority.pubkey(),
-            0,
-            vec![],
-        )],
-        Some(&config.authority.pubkey()),
+        &ixs,
+        Some(&payer),
         &Hash::new_unique(),
     );
 
@@ -98,6 +164,46 @@ pub fn send_and_confirm_transaction_with_config(
     }
 }
 
+/// Fetches `buffer_acc`, strips its `UpgradeableLoaderState::Buffer` header,
+/// and compares the remaining on-chain bytes against `program_data` at
+/// `chunk_sz` boundaries, returning the byte offset of every chunk that
+/// still needs to be written (either because it differs or the on-chain
+/// data doesn't extend that far yet).
+pub fn diff_buffer_chunks(
+    client: &RpcClient,
+    buffer_acc: Pubkey,
+    program_data: &[u8],
+    chunk_sz: usize,
+) -> Result<Vec<u32>> {
+    let account = client
+        .get_account(&buffer_acc)
+        .context("Couldn't fetch existing buffer account")?;
+
+    let header_len = UpgradeableLoaderState::size_of_buffer_metadata();
+    let on_chain = account.data.get(header_len..).unwrap_or_default();
+
+    Ok(pending_chunk_offsets(on_chain, program_data, chunk_sz))
+}
+
+/// Returns the offset of every `chunk_sz`-sized chunk of `program_data`
+/// that doesn't already match `on_chain` byte-for-byte, i.e. the chunks a
+/// resumed write still needs to send.
+fn pending_chunk_offsets(
+    on_chain: &[u8],
+    program_data: &[u8],
+    chunk_sz: usize,
+) -> Vec<u32> {
+    program_data
+        .chunks(chunk_sz)
+        .enumerate()
+        .filter(|(i, chunk)| {
+            let offset = i * chunk_sz;
+            on_chain.get(offset..offset + chunk.len()) != Some(*chunk)
+        })
+        .map(|(i, _)| (i * chunk_sz) as u32)
+        .collect()
+}
+
 pub fn term_print(s: &str) -> Result<()> {
     let mut stdout = std::io::stdout();
     queue!(stdout, cursor::SavePosition)?;
@@ -111,3 +217,68 @@ pub fn term_print(s: &str) -> Result<()> {
     )?;
     Ok(())
 }
+
+/// Prompts the user with `message` and reads a `y`/`n` answer from stdin.
+/// Used to gate irreversible operations behind an explicit confirmation.
+pub fn confirm(message: &str) -> Result<bool> {
+    print!("{message} [y/N] ");
+    std::io::stdout().flush()?;
+
+    let mut answer = String::new();
+    std::io::stdin().read_line(&mut answer)?;
+
+    Ok(matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pending_chunks_skips_chunks_that_already_match_on_chain() {
+        let program_data = vec![1, 2, 3, 4, 5, 6, 7];
+        let on_chain = vec![1, 2, 3, 9, 9, 9, 0];
+
+        // Chunk size 3: [1,2,3] matches, [4,5,6] doesn't, [7] doesn't.
+        assert_eq!(
+            pending_chunk_offsets(&on_chain, &program_data, 3),
+            vec![3, 6]
+        );
+    }
+
+    #[test]
+    fn pending_chunks_treats_missing_on_chain_tail_as_pending() {
+        let program_data = vec![1, 2, 3, 4, 5];
+        let on_chain = vec![1, 2, 3];
+
+        assert_eq!(pending_chunk_offsets(&on_chain, &program_data, 3), vec![3]);
+    }
+
+    #[test]
+    fn pending_chunks_empty_when_everything_matches() {
+        let program_data = vec![1, 2, 3, 4];
+        let on_chain = program_data.clone();
+
+        assert!(pending_chunk_offsets(&on_chain, &program_data, 2).is_empty());
+    }
+
+    #[test]
+    fn fee_percentile_picks_the_requested_rank() {
+        let fees = vec![10, 50, 30, 20, 40];
+
+        assert_eq!(fee_percentile(fees.clone(), 0), 10);
+        assert_eq!(fee_percentile(fees.clone(), 50), 30);
+        assert_eq!(fee_percentile(fees.clone(), 100), 50);
+    }
+
+    #[test]
+    fn fee_percentile_clamps_above_100() {
+        let fees = vec![10, 20, 30];
+        assert_eq!(fee_percentile(fees.clone(), 255), fee_percentile(fees, 100));
+    }
+
+    #[test]
+    fn fee_percentile_of_empty_is_zero() {
+        assert_eq!(fee_percentile(Vec::new(), 50), 0);
+    }
+}
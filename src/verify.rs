@@ -0,0 +1,169 @@
+use anyhow::{bail, Context, Result};
+use solana_sdk::{
+    bpf_loader_upgradeable::{self, UpgradeableLoaderState},
+    pubkey::Pubkey,
+};
+
+use crate::AppConfig;
+
+/// Result of comparing a deployed program's on-chain bytecode against the
+/// local `.so` referenced in `deploy.toml`.
+pub enum VerifyResult {
+    Match,
+    Mismatch {
+        /// Byte offset of the first difference, relative to the start of
+        /// the program's executable data (header stripped).
+        first_diff_offset: usize,
+        on_chain_len: usize,
+        local_len: usize,
+    },
+}
+
+/// Fetches `program_id`'s on-chain executable data, strips the upgradeable
+/// loader's `ProgramData` header, and byte-compares it against
+/// `config.program_data`, the already-verified local `.so`. Lets a caller
+/// confirm a high-load deploy actually landed the intended binary without
+/// diffing accounts by hand.
+pub fn verify_program(config: &AppConfig, program_id: &Pubkey) -> Result<VerifyResult> {
+    let (program_data_addr, _) = Pubkey::find_program_address(
+        &[program_id.as_ref()],
+        &bpf_loader_upgradeable::id(),
+    );
+
+    let account = config
+        .client
+        .get_account(&program_data_addr)
+        .with_context(|| {
+            format!("Couldn't fetch program data account for {program_id}.")
+        })?;
+
+    let header_len = UpgradeableLoaderState::size_of_programdata_metadata();
+    let on_chain = account.data.get(header_len..).unwrap_or_default();
+
+    Ok(compare_program_data(on_chain, &config.program_data))
+}
+
+/// Compares `on_chain` (the programdata account's executable bytes, header
+/// already stripped) against `local`. The programdata account is allocated
+/// with `max_data_len` room for upgrades (see `deploy_with_max_program_len`'s
+/// `* 2` sizing), so `on_chain` is typically longer than `local` and the
+/// slack is zero-padded. Compares the overlapping prefix, then requires the
+/// remainder to be all zeroes rather than demanding equal lengths.
+fn compare_program_data(on_chain: &[u8], local: &[u8]) -> VerifyResult {
+    let Some(on_chain_head) = on_chain.get(..local.len()) else {
+        let first_diff_offset = on_chain
+            .iter()
+            .zip(local.iter())
+            .position(|(a, b)| a != b)
+            .unwrap_or(on_chain.len());
+        return VerifyResult::Mismatch {
+            first_diff_offset,
+            on_chain_len: on_chain.len(),
+            local_len: local.len(),
+        };
+    };
+
+    let first_diff_offset = on_chain_head
+        .iter()
+        .zip(local.iter())
+        .position(|(a, b)| a != b)
+        .or_else(|| {
+            on_chain[local.len()..]
+                .iter()
+                .position(|&b| b != 0)
+                .map(|pos| local.len() + pos)
+        });
+
+    match first_diff_offset {
+        Some(offset) => VerifyResult::Mismatch {
+            first_diff_offset: offset,
+            on_chain_len: on_chain.len(),
+            local_len: local.len(),
+        },
+        None => VerifyResult::Match,
+    }
+}
+
+/// Prints a human-readable verdict for `result` and returns an error if it's
+/// a mismatch, so callers can use `?` to set a non-zero exit status.
+pub fn report_verify_result(program_id: &Pubkey, result: VerifyResult) -> Result<()> {
+    match result {
+        VerifyResult::Match => {
+            println!("{program_id}: on-chain bytecode matches the local binary.");
+            Ok(())
+        }
+        VerifyResult::Mismatch {
+            first_diff_offset,
+            on_chain_len,
+            local_len,
+        } => {
+            bail!(
+                "{program_id}: on-chain bytecode does NOT match the local binary \
+                 (first differing byte at offset {first_diff_offset}, \
+                 on-chain length {on_chain_len}, local length {local_len})."
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_when_on_chain_has_zero_padded_upgrade_slack() {
+        let local = vec![1, 2, 3];
+        let mut on_chain = local.clone();
+        on_chain.extend([0u8; 3]); // deploy allocates `local.len() * 2`.
+
+        assert!(matches!(
+            compare_program_data(&on_chain, &local),
+            VerifyResult::Match
+        ));
+    }
+
+    #[test]
+    fn mismatches_on_a_differing_byte_within_the_shared_prefix() {
+        let local = vec![1, 2, 3];
+        let on_chain = vec![1, 9, 3, 0, 0, 0];
+
+        let result = compare_program_data(&on_chain, &local);
+        assert!(matches!(
+            result,
+            VerifyResult::Mismatch {
+                first_diff_offset: 1,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn mismatches_on_non_zero_padding_past_the_local_length() {
+        let local = vec![1, 2, 3];
+        let on_chain = vec![1, 2, 3, 0, 7, 0];
+
+        let result = compare_program_data(&on_chain, &local);
+        assert!(matches!(
+            result,
+            VerifyResult::Mismatch {
+                first_diff_offset: 4,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn mismatches_when_on_chain_is_shorter_than_local() {
+        let local = vec![1, 2, 3, 4];
+        let on_chain = vec![1, 2, 3];
+
+        let result = compare_program_data(&on_chain, &local);
+        assert!(matches!(
+            result,
+            VerifyResult::Mismatch {
+                first_diff_offset: 3,
+                ..
+            }
+        ));
+    }
+}